@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use serde_json::{json, Value};
+
+/// Samples younger than this are kept at full 1s resolution.
+const RECENT_WINDOW_SECS: u64 = 60;
+/// Samples older than [`RECENT_WINDOW_SECS`] are averaged into buckets this wide.
+const BUCKET_WIDTH_SECS: u64 = 10;
+
+/// One retained data point: either a raw per-second sample or a
+/// `BUCKET_WIDTH_SECS`-averaged bucket of older history.
+struct JanitorEntry {
+    timestamp: u64,
+    cpu: Vec<i64>,
+    cores: Vec<Vec<i64>>,
+}
+
+/// A still-open downsample bucket, accumulated until a sample from the next
+/// bucket arrives and rolls it over into a finalized (averaged) entry.
+struct PendingBucket {
+    bucket_start: u64,
+    cpu_sum: Vec<i64>,
+    cores_sum: Vec<Vec<i64>>,
+    count: i64,
+}
+
+impl PendingBucket {
+    fn new(bucket_start: u64, cpu: &[i64], cores: &[Vec<i64>]) -> Self {
+        PendingBucket { bucket_start, cpu_sum: cpu.to_vec(), cores_sum: cores.to_vec(), count: 1 }
+    }
+
+    fn add(&mut self, cpu: &[i64], cores: &[Vec<i64>]) {
+        for (sum, v) in self.cpu_sum.iter_mut().zip(cpu) {
+            *sum += v;
+        }
+        for (sum_row, row) in self.cores_sum.iter_mut().zip(cores) {
+            for (sum, v) in sum_row.iter_mut().zip(row) {
+                *sum += v;
+            }
+        }
+        self.count += 1;
+    }
+
+    fn finalize(&self) -> JanitorEntry {
+        let avg = |sums: &Vec<i64>| sums.iter().map(|s| s / self.count).collect();
+        JanitorEntry {
+            timestamp: self.bucket_start,
+            cpu: avg(&self.cpu_sum),
+            cores: self.cores_sum.iter().map(avg).collect(),
+        }
+    }
+}
+
+/// Bounded, self-pruning time series of CPU samples. Recent samples are kept
+/// at full 1s resolution, older ones are folded into `BUCKET_WIDTH_SECS`
+/// averaged buckets, and anything past `retain_secs` is dropped entirely.
+/// Keeps `cpu_averages.json`'s on-disk footprint bounded no matter how long
+/// the process runs, instead of growing forever.
+pub(crate) struct Janitor {
+    retain_secs: u64,
+    recent: VecDeque<JanitorEntry>,
+    coarse: VecDeque<JanitorEntry>,
+    pending: Option<PendingBucket>,
+}
+
+impl Janitor {
+    pub(crate) fn new(retain_secs: u64) -> Self {
+        Janitor { retain_secs, recent: VecDeque::new(), coarse: VecDeque::new(), pending: None }
+    }
+
+    /// Records a new sample, then rolls aged-out samples into coarse buckets
+    /// and evicts anything older than `retain_secs`.
+    pub(crate) fn record(&mut self, timestamp: u64, cpu: Vec<i64>, cores: Vec<Vec<i64>>) {
+        self.recent.push_back(JanitorEntry { timestamp, cpu, cores });
+        self.roll_over(timestamp);
+        self.evict(timestamp);
+    }
+
+    /// Moves samples that fell out of the recent window into coarse buckets.
+    /// The window is `RECENT_WINDOW_SECS`, clamped to `retain_secs` so a
+    /// `--retain` shorter than the default full-resolution window doesn't
+    /// leave `recent` holding onto samples longer than the configured
+    /// retention.
+    fn roll_over(&mut self, now: u64) {
+        let window = RECENT_WINDOW_SECS.min(self.retain_secs);
+        while let Some(front) = self.recent.front() {
+            if now.saturating_sub(front.timestamp) < window {
+                break;
+            }
+            let entry = self.recent.pop_front().unwrap();
+            self.fold_into_bucket(entry);
+        }
+    }
+
+    fn fold_into_bucket(&mut self, entry: JanitorEntry) {
+        let bucket_start = entry.timestamp - entry.timestamp % BUCKET_WIDTH_SECS;
+        match &mut self.pending {
+            Some(pending) if pending.bucket_start == bucket_start => {
+                pending.add(&entry.cpu, &entry.cores);
+            }
+            _ => {
+                if let Some(pending) = self.pending.take() {
+                    self.coarse.push_back(pending.finalize());
+                }
+                self.pending = Some(PendingBucket::new(bucket_start, &entry.cpu, &entry.cores));
+            }
+        }
+    }
+
+    /// Drops coarse buckets, including the still-open pending one, whose
+    /// start is older than `retain_secs`.
+    fn evict(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(self.retain_secs);
+        while let Some(front) = self.coarse.front() {
+            if front.timestamp < cutoff {
+                self.coarse.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.pending.as_ref().map_or(false, |pending| pending.bucket_start < cutoff) {
+            self.pending = None;
+        }
+    }
+
+    /// Atomically rewrites `path` with the current retained history: write to
+    /// a temp file, then rename over the target, so a crash mid-write can
+    /// never leave a truncated/corrupt JSON file behind.
+    pub(crate) fn flush(&self, path: &str) -> io::Result<()> {
+        let mut entries: Vec<&JanitorEntry> = self.coarse.iter().collect();
+        let pending_entry = self.pending.as_ref().map(PendingBucket::finalize);
+        if let Some(entry) = &pending_entry {
+            entries.push(entry);
+        }
+        entries.extend(self.recent.iter());
+
+        let json_array: Vec<Value> = entries.iter().map(|entry| {
+            json!({
+                entry.timestamp.to_string(): {
+                    "cpu": entry.cpu,
+                    "cores": entry.cores,
+                }
+            })
+        }).collect();
+
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, serde_json::to_string(&json_array)?)?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-core sample whose cpu/core values equal `ts`, so bucket averages
+    /// are easy to hand-check.
+    fn sample(ts: u64) -> (Vec<i64>, Vec<Vec<i64>>) {
+        (vec![ts as i64], vec![vec![ts as i64]])
+    }
+
+    #[test]
+    fn keeps_only_the_recent_window_at_full_resolution() {
+        let mut janitor = Janitor::new(3600);
+        for ts in 0..90 {
+            let (cpu, cores) = sample(ts);
+            janitor.record(ts, cpu, cores);
+        }
+
+        // now = 89; anything with `89 - timestamp >= 60` has rolled out of `recent`.
+        assert_eq!(janitor.recent.len(), 60);
+        assert_eq!(janitor.recent.front().unwrap().timestamp, 30);
+        assert_eq!(janitor.recent.back().unwrap().timestamp, 89);
+    }
+
+    #[test]
+    fn folds_aged_out_samples_into_ten_second_buckets() {
+        let mut janitor = Janitor::new(3600);
+        for ts in 0..90 {
+            let (cpu, cores) = sample(ts);
+            janitor.record(ts, cpu, cores);
+        }
+
+        // Samples 0..=29 rolled out of `recent`; 0..=9 and 10..=19 finalized
+        // into coarse buckets, 20..=29 still open as `pending`.
+        assert_eq!(janitor.coarse.len(), 2);
+        assert_eq!(janitor.coarse[0].timestamp, 0);
+        assert_eq!(janitor.coarse[0].cpu, vec![4]); // avg(0..=9) = 4.5, truncated
+        assert_eq!(janitor.coarse[1].timestamp, 10);
+        assert_eq!(janitor.coarse[1].cpu, vec![14]); // avg(10..=19) = 14.5, truncated
+
+        let pending = janitor.pending.as_ref().expect("bucket 20..=29 still open");
+        assert_eq!(pending.bucket_start, 20);
+        assert_eq!(pending.count, 10);
+    }
+
+    #[test]
+    fn retain_secs_shorter_than_the_recent_window_is_still_honored() {
+        let mut janitor = Janitor::new(10);
+        for ts in 0..100 {
+            let (cpu, cores) = sample(ts);
+            janitor.record(ts, cpu, cores);
+        }
+
+        // now = 99, retain_secs = 10 -> nothing older than 89 should survive
+        // anywhere, `recent` included.
+        for entry in &janitor.recent {
+            assert!(entry.timestamp >= 89, "recent sample {} should have rolled over", entry.timestamp);
+        }
+        for entry in &janitor.coarse {
+            assert!(entry.timestamp >= 89, "bucket {} should have been evicted", entry.timestamp);
+        }
+    }
+
+    #[test]
+    fn evicts_coarse_buckets_older_than_the_retention_window() {
+        let mut janitor = Janitor::new(50);
+        for ts in 0..200 {
+            let (cpu, cores) = sample(ts);
+            janitor.record(ts, cpu, cores);
+        }
+
+        // now = 199, retain_secs = 50 -> cutoff = 149.
+        for entry in &janitor.coarse {
+            assert!(entry.timestamp >= 149, "bucket {} should have been evicted", entry.timestamp);
+        }
+    }
+
+    #[test]
+    fn flush_writes_one_json_entry_per_retained_sample() {
+        let mut janitor = Janitor::new(3600);
+        for ts in 0..25 {
+            let (cpu, cores) = sample(ts);
+            janitor.record(ts, cpu, cores);
+        }
+
+        let path = std::env::temp_dir().join(format!("janitor_test_{}.json", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        janitor.flush(path_str).unwrap();
+
+        let contents = fs::read_to_string(path_str).unwrap();
+        let parsed: Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 25);
+
+        fs::remove_file(path_str).unwrap();
+    }
+}
@@ -1,55 +1,192 @@
 use std::env;
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use clearscreen::clear;
-use serde_json::{json, Value};
+use serde_json::Value;
 use chrono::prelude::*;
 
-fn get_cpu_times() -> (Vec<u64>, Vec<Vec<u64>>) {
-    let file = File::open("/proc/stat").unwrap();
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
-
-    let cpu_times: Vec<u64> = lines.next().unwrap().unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    let mut core_times = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        if line.starts_with("cpu") {
-            let times: Vec<u64> = line
-                .split_whitespace()
-                .skip(1)
-                .map(|x| x.parse().unwrap())
-                .collect();
-            core_times.push(times);
-        } else {
-            break;
+mod platform;
+mod janitor;
+mod tui;
+use platform::make_harvester;
+use janitor::Janitor;
+
+/// Default on-disk location for the retained CPU history.
+const CPU_AVERAGES_PATH: &str = "cpu_averages.json";
+/// Default retention window for `--retain`, in seconds.
+const DEFAULT_RETAIN_SECS: u64 = 3600;
+/// How often `cpu_averages.json` gets rewritten. The janitor's in-memory
+/// buffer is already updated every tick; re-serializing and rewriting the
+/// whole retained history to disk that often is needless churn, so flushing
+/// is throttled to this cadence instead.
+const FLUSH_INTERVAL_SECS: u64 = 10;
+
+/// Jiffie counters for one CPU (aggregate or a single core), as laid out in
+/// `/proc/stat`. Parsed defensively since kernels before 2.6.33 don't expose
+/// `guest`/`guest_nice` and may stop even earlier.
+#[derive(Debug, Clone, Default)]
+struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+    guest: u64,
+    guest_nice: u64,
+}
+
+impl CpuTimes {
+    /// Builds a `CpuTimes` from the whitespace-split fields following the
+    /// `cpuN` label. Any field missing from the line (older kernels) defaults
+    /// to 0 instead of panicking.
+    fn parse(fields: &[u64]) -> Self {
+        let field = |i: usize| fields.get(i).copied().unwrap_or(0);
+        CpuTimes {
+            user: field(0),
+            nice: field(1),
+            system: field(2),
+            idle: field(3),
+            iowait: field(4),
+            irq: field(5),
+            softirq: field(6),
+            steal: field(7),
+            guest: field(8),
+            guest_nice: field(9),
         }
     }
 
-    (cpu_times, core_times)
+    /// Time spent idle, including iowait.
+    fn idle_total(&self) -> u64 {
+        self.idle + self.iowait
+    }
+
+    /// Total jiffies across all states. `guest` and `guest_nice` are already
+    /// counted inside `user`/`nice` by the kernel, so they must not be added
+    /// again here.
+    fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+
+    fn busy(&self) -> u64 {
+        self.total() - self.idle_total()
+    }
+
+    /// Field values in `/proc/stat` column order, for display and diffing.
+    fn as_jiffies(&self) -> [i64; 10] {
+        [
+            self.user as i64, self.nice as i64, self.system as i64, self.idle as i64,
+            self.iowait as i64, self.irq as i64, self.softirq as i64, self.steal as i64,
+            self.guest as i64, self.guest_nice as i64,
+        ]
+    }
+
+    /// The mirror of [`CpuTimes::as_jiffies`]: rebuilds a `CpuTimes` from a
+    /// jiffie diff (or a sum of diffs) in the same column order, so callers
+    /// can reuse `total()`/`busy()` instead of re-deriving the busy/idle
+    /// split by hand. Diffs are never negative in practice (the counters are
+    /// monotonic), so anything below 0 is treated as 0.
+    fn from_jiffies(diff: &[i64]) -> Self {
+        let field = |i: usize| diff.get(i).copied().unwrap_or(0).max(0) as u64;
+        CpuTimes {
+            user: field(0), nice: field(1), system: field(2), idle: field(3),
+            iowait: field(4), irq: field(5), softirq: field(6), steal: field(7),
+            guest: field(8), guest_nice: field(9),
+        }
+    }
+}
+
+/// Busy/idle usage formula (`busy_delta / total_delta * 100`, see
+/// [`CpuTimes::total`]/[`CpuTimes::busy`]), applied to an already-summed
+/// jiffie diff in `/proc/stat` column order.
+fn jiffies_usage_percent(diff: &[i64]) -> f32 {
+    let times = CpuTimes::from_jiffies(diff);
+    let total = times.total();
+    if total == 0 {
+        return 0.0;
+    }
+    (times.busy() as f32 / total as f32) * 100.0
 }
 
-fn calculate_time_diff(prev: &[u64], current: &[u64]) -> Vec<i64> {
-    current.iter().zip(prev.iter())
-        .map(|(curr, prev)| *curr as i64 - *prev as i64)
-        .collect()
+fn calculate_time_diff(prev: &CpuTimes, current: &CpuTimes) -> [i64; 10] {
+    let prev = prev.as_jiffies();
+    let current = current.as_jiffies();
+    let mut diff = [0i64; 10];
+    for i in 0..10 {
+        diff[i] = current[i] - prev[i];
+    }
+    diff
+}
+
+const GRAPH_RAMP: [char; 8] = ['\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}'];
+const GRAPH_HISTORY: usize = 32;
+
+/// Fixed-capacity ring buffer of recent utilization percentages for one CPU.
+struct UtilHistory {
+    data: Vec<f32>,
+    idx: usize,
+    size: usize,
 }
 
-fn store_values(cpu_times: &[u64], core_times: &[Vec<u64>], stored_values: &mut Vec<(u64, Vec<u64>, Vec<Vec<u64>>)>) {
+impl UtilHistory {
+    fn new() -> Self {
+        UtilHistory { data: vec![0.0; GRAPH_HISTORY], idx: 0, size: 0 }
+    }
+
+    /// Overwrites the oldest slot with the latest utilization sample.
+    fn sample(&mut self, pct: f32) {
+        self.data[self.idx] = pct;
+        self.idx = (self.idx + 1) % GRAPH_HISTORY;
+        self.size = (self.size + 1).min(GRAPH_HISTORY);
+    }
+
+    /// Renders the stored samples oldest-to-newest as a block-glyph bar.
+    fn render(&self) -> String {
+        let mut bar = String::with_capacity(self.size);
+        let start = (self.idx + GRAPH_HISTORY - self.size) % GRAPH_HISTORY;
+        for i in 0..self.size {
+            let pct = self.data[(start + i) % GRAPH_HISTORY];
+            if pct <= 0.0 {
+                bar.push(' ');
+            } else {
+                let level = ((pct / 100.0) * 8.0).floor().min(7.0) as usize;
+                bar.push(GRAPH_RAMP[level]);
+            }
+        }
+        bar
+    }
+}
+
+/// Prints a live sparkline view of recent per-core and aggregate utilization.
+fn print_graph(avg_history: &mut UtilHistory, core_histories: &mut Vec<UtilHistory>, cpu_avgs: &[i64], core_avgs: &[Vec<i64>]) {
+    while core_histories.len() < core_avgs.len() {
+        core_histories.push(UtilHistory::new());
+    }
+
+    avg_history.sample(jiffies_usage_percent(cpu_avgs));
+
+    println!("CPU load history (last {} samples):", GRAPH_HISTORY);
+    println!("{:<5} {}", "avg", avg_history.render());
+
+    for (i, core_avg) in core_avgs.iter().enumerate() {
+        core_histories[i].sample(jiffies_usage_percent(core_avg));
+        println!("{:<5} {}", format!("cpu{}", i), core_histories[i].render());
+    }
+    println!();
+}
+
+fn store_values(cpu_times: &CpuTimes, core_times: &[CpuTimes], stored_values: &mut Vec<(u64, CpuTimes, Vec<CpuTimes>)>) {
     let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-    stored_values.push((timestamp, cpu_times.to_vec(), core_times.to_vec()));
+    stored_values.push((timestamp, cpu_times.clone(), core_times.to_vec()));
 }
 
 
 fn read_json_file(timestamp: Option<&str>) {
-    let file = File::open("cpu_averages.json").unwrap();
+    let file = File::open(CPU_AVERAGES_PATH).unwrap();
     let reader = BufReader::new(file);
 
     let mut json_data = String::new();
@@ -83,12 +220,12 @@ fn format_timestamp(timestamp: u64) -> String {
 
 
 
-fn print_values(stored_values: &Vec<(u64, Vec<u64>, Vec<Vec<u64>>)>, print_avg: bool, num_times: usize) {
+fn print_values(stored_values: &[(u64, CpuTimes, Vec<CpuTimes>)], print_avg: bool, num_times: usize, graph: Option<(&mut UtilHistory, &mut Vec<UtilHistory>)>) -> Option<(Vec<i64>, Vec<Vec<i64>>)> {
     let num_values = stored_values.len();
 
     if num_values < 2 {
         println!("Not enough stored values to print differences.");
-        return;
+        return None;
     }
 
     let start_index = if print_avg { num_values.saturating_sub(num_times) } else { num_values - 2 };
@@ -145,49 +282,26 @@ fn print_values(stored_values: &Vec<(u64, Vec<u64>, Vec<Vec<u64>>)>, print_avg:
     }
     println!();
 
+    if let Some((avg_history, core_histories)) = graph {
+        print_graph(avg_history, core_histories, &cpu_avgs, &core_avgs);
+    }
+
     // Calculate and print percentage usage only if not in average mode
     if !print_avg {
         println!("CPU Usage Percentages:");
         println!("{:>5} {:>10}", "CPU", "Usage %");
         
-        let total_time: i64 = cpu_avgs.iter().sum();
-        let idle_time = cpu_avgs[3] + cpu_avgs[4];
-        let usage_percent = (1.0 - idle_time as f32 / total_time as f32) * 100.0;
-        println!("{:<5} {:>10.2}%", "avg", usage_percent);
+        let usage = jiffies_usage_percent(&cpu_avgs);
+        println!("{:<5} {:>10.2}%", "avg", usage);
 
         for (i, core_avg) in core_avgs.iter().enumerate() {
-            let total_time: i64 = core_avg.iter().sum();
-            let idle_time = core_avg[3] + core_avg[4];
-            let usage_percent = (1.0 - idle_time as f32 / total_time as f32) * 100.0;
-            println!("{:<5} {:>10.2}%", format!("cpu{}", i), usage_percent);
+            let usage = jiffies_usage_percent(core_avg);
+            println!("{:<5} {:>10.2}%", format!("cpu{}", i), usage);
         }
         println!();
     }
 
-    // Store to JSON file (unchanged)
-    let json_data = json!({
-        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs().to_string(): {
-            "cpu": cpu_avgs,
-            "cores": core_avgs
-        }
-    });
-
-    let file_path = "cpu_averages.json";
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(file_path)
-        .unwrap();
-
-    if file.metadata().unwrap().len() == 0 {
-        write!(file, "[").unwrap();
-    } else {
-        file.seek(SeekFrom::End(-1)).unwrap();
-        write!(file, ",").unwrap();
-    }
-
-    writeln!(file, "{}", json_data.to_string()).unwrap();
-    write!(file, "]").unwrap();
+    Some((cpu_avgs, core_avgs))
 }
 
 fn print_json_data(timestamp: &str, data: &Value) {
@@ -225,6 +339,11 @@ fn main() {
         .and_then(|i| args.get(i + 1))
         .and_then(|s| s.parse().ok())
         .unwrap_or(10);
+    let retain_secs = args.iter()
+        .position(|arg| arg == "--retain")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|s| s.trim_end_matches('s').parse().ok())
+        .unwrap_or(DEFAULT_RETAIN_SECS);
 
     if args.contains(&"--read".to_string()) {
         let timestamp = args.iter().position(|arg| arg == "--read").and_then(|i| args.get(i + 1));
@@ -233,11 +352,23 @@ fn main() {
     }
 
     let mut stored_values = Vec::new();
+    let mut harvester = make_harvester();
+
+    if args.contains(&"--tui".to_string()) {
+        tui::run(harvester, stored_values).expect("TUI dashboard failed");
+        return;
+    }
+
+    let graph_mode = args.contains(&"--graph".to_string());
+    let mut avg_history = UtilHistory::new();
+    let mut core_histories: Vec<UtilHistory> = Vec::new();
+    let mut janitor = Janitor::new(retain_secs);
+    let mut last_flush = 0u64;
 
     loop {
         let start = Instant::now();
 
-        let (cpu_times, core_times) = get_cpu_times();
+        let (cpu_times, core_times) = harvester.sample();
 
         store_values(&cpu_times, &core_times, &mut stored_values);
 
@@ -245,12 +376,19 @@ fn main() {
             clear().expect("Failed to clear screen");
         }
 
-        print_values(&stored_values, print_avg, num_times);
+        let graph = if graph_mode { Some((&mut avg_history, &mut core_histories)) } else { None };
+        if let Some((cpu_avgs, core_avgs)) = print_values(&stored_values, print_avg, num_times, graph) {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            janitor.record(timestamp, cpu_avgs, core_avgs);
+            if timestamp.saturating_sub(last_flush) >= FLUSH_INTERVAL_SECS {
+                janitor.flush(CPU_AVERAGES_PATH).expect("Failed to flush cpu_averages.json");
+                last_flush = timestamp;
+            }
+        }
 
-        // Keep only the last 'num_times' measurements if print_avg is true
-        if print_avg && stored_values.len() > num_times {
-            stored_values.remove(0);
-        } else if !print_avg && stored_values.len() > 2 {
+        // Keep only the last 'num_times' measurements if print_avg is true, else the last 2.
+        let cap = if print_avg { num_times } else { 2 };
+        if stored_values.len() > cap {
             stored_values.remove(0);
         }
 
@@ -259,4 +397,87 @@ fn main() {
             thread::sleep(Duration::from_secs(1) - elapsed);
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_defaults_missing_trailing_fields_to_zero() {
+        // A pre-2.6.33 line with no guest/guest_nice columns.
+        let fields = [100u64, 10, 50, 800, 5, 0, 0, 0];
+        let times = CpuTimes::parse(&fields);
+
+        assert_eq!(times.user, 100);
+        assert_eq!(times.iowait, 5);
+        assert_eq!(times.guest, 0);
+        assert_eq!(times.guest_nice, 0);
+    }
+
+    #[test]
+    fn busy_and_total_exclude_guest_and_guest_nice_from_double_counting() {
+        let fields = [100u64, 10, 50, 800, 5, 0, 0, 0, 20, 2];
+        let times = CpuTimes::parse(&fields);
+
+        // total() must not add guest/guest_nice again: the kernel already
+        // folds them into user/nice.
+        assert_eq!(times.total(), 100 + 10 + 50 + 800 + 5);
+        assert_eq!(times.idle_total(), 800 + 5);
+        assert_eq!(times.busy(), times.total() - times.idle_total());
+    }
+
+    #[test]
+    fn jiffies_usage_percent_matches_busy_over_total() {
+        let prev = CpuTimes::parse(&[100, 0, 0, 800, 0, 0, 0, 0, 0, 0]);
+        let current = CpuTimes::parse(&[150, 0, 0, 850, 0, 0, 0, 0, 0, 0]);
+
+        let diff = calculate_time_diff(&prev, &current);
+        // busy delta = 50, total delta = 100 -> 50%.
+        assert_eq!(jiffies_usage_percent(&diff), 50.0);
+    }
+
+    #[test]
+    fn jiffies_usage_percent_is_zero_for_a_zero_total_diff() {
+        assert_eq!(jiffies_usage_percent(&[0; 10]), 0.0);
+    }
+
+    /// Mirrors the glyph-selection formula in `UtilHistory::render`.
+    fn expected_glyph(pct: f32) -> char {
+        if pct <= 0.0 {
+            ' '
+        } else {
+            let level = ((pct / 100.0) * 8.0).floor().min(7.0) as usize;
+            GRAPH_RAMP[level]
+        }
+    }
+
+    #[test]
+    fn util_history_renders_oldest_to_newest_before_it_fills_up() {
+        let mut history = UtilHistory::new();
+        history.sample(0.0);
+        history.sample(50.0);
+        history.sample(100.0);
+
+        let rendered: Vec<char> = history.render().chars().collect();
+        assert_eq!(rendered, vec![expected_glyph(0.0), expected_glyph(50.0), expected_glyph(100.0)]);
+    }
+
+    #[test]
+    fn util_history_wraps_without_losing_ordering() {
+        let mut history = UtilHistory::new();
+        for i in 0..(GRAPH_HISTORY + 5) {
+            history.sample(i as f32);
+        }
+
+        // Capacity caps at GRAPH_HISTORY and the ring has wrapped by 5 slots,
+        // overwriting the oldest 5 samples (0..=4).
+        assert_eq!(history.size, GRAPH_HISTORY);
+        assert_eq!(history.idx, 5);
+
+        let rendered: Vec<char> = history.render().chars().collect();
+        assert_eq!(rendered.len(), GRAPH_HISTORY);
+        let expected: Vec<char> = (5..(GRAPH_HISTORY + 5)).map(|i| expected_glyph(i as f32)).collect();
+        assert_eq!(rendered, expected);
+    }
 }
\ No newline at end of file
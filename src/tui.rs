@@ -0,0 +1,266 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols;
+use ratatui::text::Span;
+use ratatui::widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, Table};
+use ratatui::{Frame, Terminal};
+
+use crate::platform::CpuHarvester;
+use crate::{calculate_time_diff, jiffies_usage_percent, store_values, CpuTimes};
+
+/// Number of utilization samples kept per chart line.
+const CHART_HISTORY: usize = 120;
+const MIN_REFRESH: Duration = Duration::from_millis(100);
+const MAX_REFRESH: Duration = Duration::from_secs(5);
+const REFRESH_STEP: Duration = Duration::from_millis(100);
+
+const CORE_COLORS: [Color; 6] =
+    [Color::Cyan, Color::Magenta, Color::Yellow, Color::Green, Color::Blue, Color::Red];
+
+/// Refresh cadence, average toggle, scroll position, and the rolling
+/// utilization history each chart line is drawn from.
+struct TuiState {
+    refresh: Duration,
+    show_average: bool,
+    scroll: usize,
+    avg_history: Vec<f64>,
+    core_history: Vec<Vec<f64>>,
+    latest_cpu_jiffies: Vec<i64>,
+    latest_core_jiffies: Vec<Vec<i64>>,
+}
+
+impl TuiState {
+    fn new() -> Self {
+        TuiState {
+            refresh: Duration::from_secs(1),
+            show_average: false,
+            scroll: 0,
+            avg_history: Vec::new(),
+            core_history: Vec::new(),
+            latest_cpu_jiffies: Vec::new(),
+            latest_core_jiffies: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, cpu_avgs: &[i64], core_avgs: &[Vec<i64>]) {
+        push_capped(&mut self.avg_history, jiffies_usage_percent(cpu_avgs) as f64);
+
+        while self.core_history.len() < core_avgs.len() {
+            self.core_history.push(Vec::new());
+        }
+        for (history, core_avg) in self.core_history.iter_mut().zip(core_avgs) {
+            push_capped(history, jiffies_usage_percent(core_avg) as f64);
+        }
+
+        self.latest_cpu_jiffies = cpu_avgs.to_vec();
+        self.latest_core_jiffies = core_avgs.to_vec();
+    }
+}
+
+fn push_capped(history: &mut Vec<f64>, value: f64) {
+    history.push(value);
+    if history.len() > CHART_HISTORY {
+        history.remove(0);
+    }
+}
+
+/// Same windowed-diff computation as `print_values`, but averaged over the
+/// whole retained buffer when `average` is set rather than over `--times`.
+fn windowed_diffs(stored_values: &[(u64, CpuTimes, Vec<CpuTimes>)], average: bool) -> Option<(Vec<i64>, Vec<Vec<i64>>)> {
+    let num_values = stored_values.len();
+    if num_values < 2 {
+        return None;
+    }
+
+    let start_index = if average { 0 } else { num_values - 2 };
+
+    let mut cpu_avgs = vec![0i64; 10];
+    let mut core_avgs: Vec<Vec<i64>> = vec![vec![0; 10]; stored_values[0].2.len()];
+    let mut count = 0i64;
+
+    for window in stored_values.windows(2).skip(start_index) {
+        let (_, prev_cpu, prev_cores) = &window[0];
+        let (_, curr_cpu, curr_cores) = &window[1];
+
+        let cpu_diff = calculate_time_diff(prev_cpu, curr_cpu);
+        for (i, diff) in cpu_diff.iter().enumerate() {
+            cpu_avgs[i] += *diff;
+        }
+        for (j, (prev_core, curr_core)) in prev_cores.iter().zip(curr_cores.iter()).enumerate() {
+            let core_diff = calculate_time_diff(prev_core, curr_core);
+            for (i, diff) in core_diff.iter().enumerate() {
+                core_avgs[j][i] += *diff;
+            }
+        }
+        count += 1;
+    }
+
+    if average && count > 0 {
+        for avg in &mut cpu_avgs {
+            *avg /= count;
+        }
+        for core_avg in &mut core_avgs {
+            for avg in core_avg {
+                *avg /= count;
+            }
+        }
+    }
+
+    Some((cpu_avgs, core_avgs))
+}
+
+/// Runs the interactive `--tui` dashboard until `q` is pressed. Feeds off the
+/// same harvester and `stored_values` buffer the text/`--graph` modes use, so
+/// switching modes doesn't change how samples are gathered, only how they're
+/// displayed.
+pub(crate) fn run(mut harvester: Box<dyn CpuHarvester>, mut stored_values: Vec<(u64, CpuTimes, Vec<CpuTimes>)>) -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut state = TuiState::new();
+    let mut last_tick = Instant::now() - state.refresh;
+
+    let result = run_loop(&mut terminal, &mut harvester, &mut stored_values, &mut state, &mut last_tick);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    harvester: &mut Box<dyn CpuHarvester>,
+    stored_values: &mut Vec<(u64, CpuTimes, Vec<CpuTimes>)>,
+    state: &mut TuiState,
+    last_tick: &mut Instant,
+) -> io::Result<()> {
+    loop {
+        if event::poll(Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Char('a') => state.show_average = !state.show_average,
+                    KeyCode::Char('+') => state.refresh = (state.refresh + REFRESH_STEP).min(MAX_REFRESH),
+                    KeyCode::Char('-') => state.refresh = state.refresh.saturating_sub(REFRESH_STEP).max(MIN_REFRESH),
+                    KeyCode::Up => state.scroll = state.scroll.saturating_sub(1),
+                    KeyCode::Down => state.scroll += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        if last_tick.elapsed() >= state.refresh {
+            let (cpu_times, core_times) = harvester.sample();
+            store_values(&cpu_times, &core_times, stored_values);
+            if stored_values.len() > CHART_HISTORY + 1 {
+                stored_values.remove(0);
+            }
+
+            if let Some((cpu_avgs, core_avgs)) = windowed_diffs(stored_values, state.show_average) {
+                state.record(&cpu_avgs, &core_avgs);
+            }
+
+            *last_tick = Instant::now();
+        }
+
+        terminal.draw(|frame| draw(frame, state))?;
+    }
+}
+
+fn draw(frame: &mut Frame, state: &TuiState) {
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(55), Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.size());
+
+    draw_chart(frame, areas[0], state);
+    draw_table(frame, areas[1], state);
+    draw_footer(frame, areas[2], state);
+}
+
+fn draw_chart(frame: &mut Frame, area: ratatui::layout::Rect, state: &TuiState) {
+    let as_points = |history: &[f64]| -> Vec<(f64, f64)> {
+        history.iter().enumerate().map(|(i, v)| (i as f64, *v)).collect()
+    };
+
+    let avg_points = as_points(&state.avg_history);
+    let core_points: Vec<Vec<(f64, f64)>> = state.core_history.iter().map(|h| as_points(h)).collect();
+
+    let mut datasets = vec![Dataset::default()
+        .name("avg")
+        .graph_type(GraphType::Line)
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .data(&avg_points)];
+
+    for (i, points) in core_points.iter().enumerate() {
+        datasets.push(
+            Dataset::default()
+                .name(format!("cpu{}", i))
+                .graph_type(GraphType::Line)
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(CORE_COLORS[i % CORE_COLORS.len()]))
+                .data(points),
+        );
+    }
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().title("CPU utilization").borders(Borders::ALL))
+        .x_axis(Axis::default().bounds([0.0, CHART_HISTORY as f64]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]).labels(vec![Span::raw("0"), Span::raw("50"), Span::raw("100")]));
+
+    frame.render_widget(chart, area);
+}
+
+/// Column headers for the jiffie table, in `/proc/stat` order. Column widths
+/// below are derived from these so a header is never truncated.
+const JIFFIE_COLUMNS: [&str; 11] = [
+    "CPU", "user", "nice", "system", "idle", "iowait", "irq", "softirq", "steal", "guest", "guest_nice",
+];
+
+fn draw_table(frame: &mut Frame, area: ratatui::layout::Rect, state: &TuiState) {
+    let header = Row::new(JIFFIE_COLUMNS.iter().map(|h| Cell::from(*h)));
+
+    let visible = area.height.saturating_sub(3) as usize;
+    let scroll = state.scroll.min(state.latest_core_jiffies.len().saturating_sub(visible.max(1)));
+
+    let jiffie_row = |label: String, jiffies: &[i64]| {
+        let mut cells = vec![Cell::from(label)];
+        cells.extend(jiffies.iter().map(|v| Cell::from(v.to_string())));
+        Row::new(cells)
+    };
+
+    let mut rows = vec![jiffie_row("cpu".to_string(), &state.latest_cpu_jiffies)];
+    rows.extend(
+        state.latest_core_jiffies.iter().enumerate().skip(scroll).take(visible)
+            .map(|(i, jiffies)| jiffie_row(format!("cpu{}", i), jiffies)),
+    );
+
+    // Wide enough for the header plus up to 10-digit jiffie counts, whichever is longer.
+    let widths = JIFFIE_COLUMNS.map(|h| Constraint::Length((h.len() as u16 + 1).max(11)));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().title("Per-core jiffies (↑/↓ to scroll)").borders(Borders::ALL));
+
+    frame.render_widget(table, area);
+}
+
+fn draw_footer(frame: &mut Frame, area: ratatui::layout::Rect, state: &TuiState) {
+    let mode = if state.show_average { "average" } else { "instant" };
+    let text = format!(
+        "q quit  a toggle average ({})  +/- refresh ({:?})  ↑/↓ scroll cores",
+        mode, state.refresh
+    );
+    frame.render_widget(ratatui::widgets::Paragraph::new(text), area);
+}